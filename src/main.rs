@@ -1,73 +1,327 @@
-use std::{convert::TryInto, default, mem, vec};
+use std::{io, mem, vec};
 
 const PREAMBLE_LEN: usize = 7;
 const PREAMBLE: &[u8] = &[0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55];
 const SFD: u8 = 0b10101011;
 
+/// TPID of an IEEE 802.1Q VLAN tag. When this shows up in the field right
+/// after the source MAC, what follows isn't a length or an EtherType yet —
+/// it's a 4-byte tag (TCI + the real EtherType) sitting in front of them.
+const ETHERTYPE_VLAN: u16 = 0x8100;
+
+/// The dividing line between an IEEE 802.3 length field and an Ethernet II
+/// EtherType: values below this are a byte count, values at or above it name
+/// a protocol.
+const ETHERTYPE_THRESHOLD: u16 = 0x0600;
+
+/// A minimal byte sink that the encoder and decoder write into, instead of
+/// assuming the caller wants a heap-allocated `Vec<u8>`.
+///
+/// Mirrors the streaming style of Mercurial's path encoder: a couple of small
+/// methods any buffer-like type can implement, so the framing logic doesn't
+/// have to know or care whether the bytes end up in a `Vec`, a fixed-size
+/// buffer, or a socket.
+trait Sink {
+    fn write_byte(&mut self, b: u8);
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.write_byte(b);
+        }
+    }
+}
+
+impl Sink for Vec<u8> {
+    fn write_byte(&mut self, b: u8) {
+        self.push(b);
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+/// A fixed-capacity [`Sink`] for contexts that can't allocate. Bytes that
+/// don't fit are dropped and counted in `overflowed` rather than panicking.
+struct FixedSink<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    overflowed: usize,
+}
+
+impl<const N: usize> Default for FixedSink<N> {
+    fn default() -> Self {
+        FixedSink {
+            buf: [0; N],
+            len: 0,
+            overflowed: 0,
+        }
+    }
+}
+
+impl<const N: usize> FixedSink<N> {
+    fn filled(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl<const N: usize> Sink for FixedSink<N> {
+    fn write_byte(&mut self, b: u8) {
+        if self.len < N {
+            self.buf[self.len] = b;
+            self.len += 1;
+        } else {
+            self.overflowed += 1;
+        }
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for FixedSink<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.filled()
+    }
+}
+
+/// Adapts any `std::io::Write` into a [`Sink`], e.g. for streaming an encoded
+/// frame straight onto a socket instead of buffering it in memory first.
+struct IoSink<W> {
+    inner: W,
+}
+
+impl<W: io::Write> IoSink<W> {
+    fn new(inner: W) -> Self {
+        IoSink { inner }
+    }
+}
+
+impl<W: io::Write> Sink for IoSink<W> {
+    fn write_byte(&mut self, b: u8) {
+        let _ = self.inner.write_all(&[b]);
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        let _ = self.inner.write_all(bytes);
+    }
+}
+
 fn main() {
     let data: &[u8; 18] = &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 1, 2, 3, 4, 99, 99, 99, 99];
     let data2: &[u8; 14] = &[4, 5, 6, 7, 8, 9, 1, 2, 3, 4, 99, 99, 99, 99];
     let mut frames = vec![];
 
-    let mut decoder = Decoder::new();
+    let mut decoder: Decoder<Vec<u8>> = Decoder::new();
 
     for b in data {
-        if let Some(frame) = decoder.recv_byte(*b) {
-            frames.push(frame);
+        match decoder.recv_byte(*b) {
+            Ok(Some(frame)) => frames.push(frame),
+            Ok(None) => {}
+            Err(e) => eprintln!("dropping frame: {}", e),
         }
     }
 
     for b in data2 {
-        if let Some(frame) = decoder.recv_byte(*b) {
-            frames.push(frame);
+        match decoder.recv_byte(*b) {
+            Ok(Some(frame)) => frames.push(frame),
+            Ok(None) => {}
+            Err(e) => eprintln!("dropping frame: {}", e),
         }
     }
 
     for frame in frames {
         println!("{:#?}", frame);
     }
+
+    // Round-trip a frame through the Encoder to show it produces bytes the
+    // Decoder above can read back, into each of the Sink implementations.
+    let outgoing = EthFrame {
+        rx_mac: [0x02, 0x00, 0x00, 0x00, 0x00, 0x01],
+        tx_mac: [0x02, 0x00, 0x00, 0x00, 0x00, 0x02],
+        tag: Tag::Ethernet2 { ethertype: 0x0800 },
+        payload: vec![0xAB; MIN_PAYLOAD_LEN],
+    };
+
+    let wire = Encoder::encode(&outgoing);
+
+    let mut fixed: FixedSink<128> = FixedSink::default();
+    Encoder::encode_into(&outgoing, &mut fixed);
+    assert_eq!(fixed.filled(), wire.as_slice());
+
+    let mut stdout_bytes = Vec::new();
+    Encoder::encode_into(&outgoing, &mut IoSink::new(&mut stdout_bytes));
+    assert_eq!(stdout_bytes, wire);
+
+    // FrameReader drives the same Decoder for us, one whole frame at a time,
+    // instead of requiring the byte-by-byte loop above.
+    for result in FrameReader::new(wire.as_slice()) {
+        match result {
+            Ok(frame) => println!("{:#?}", frame),
+            Err(e) => eprintln!("dropping frame: {}", e),
+        }
+    }
+
+    let mut roundtrip_decoder: Decoder<Vec<u8>> = Decoder::new();
+    for &b in &wire {
+        let _ = roundtrip_decoder.recv_byte(b);
+    }
+    println!("last CRC residue: {:?}", roundtrip_decoder.last_crc_residue());
+
+    // FrameDecode is the one-shot counterpart to feeding a Decoder by hand.
+    if let Some(frame) = EthFrame::frame_decode(&wire) {
+        println!("frame_decode: {:#?}", frame);
+    }
+
+    // AsyncFrameReader is the Stream-based mirror of FrameReader, for callers
+    // built on futures::io::AsyncRead instead of std::io::Read.
+    #[cfg(feature = "async")]
+    {
+        use futures::stream::StreamExt;
+
+        let mut async_reader = AsyncFrameReader::new(futures::io::Cursor::new(wire.clone()));
+        while let Some(result) = futures::executor::block_on(async_reader.next()) {
+            match result {
+                Ok(frame) => println!("{:#?}", frame),
+                Err(e) => eprintln!("dropping frame: {}", e),
+            }
+        }
+    }
 }
 
-struct Decoder {
+/// The residue a correct CRC-32 leaves behind once the received FCS itself is
+/// folded into the running hash: `crc(frame_bytes ++ received_fcs)` equals
+/// this constant for every uncorrupted frame, init/reflection/final-XOR and
+/// all. This is the well-known magic check value for the reflected CRC-32
+/// used by Ethernet (and zlib/gzip/PNG); it only holds when the FCS is fed in
+/// least-significant-byte first, matching how it's actually transmitted on
+/// the wire. Comparing against it sidesteps ever having to reassemble the FCS
+/// bytes into an integer and get their order wrong.
+const CRC32_RESIDUE: u32 = 0x2144DF1C;
+
+/// Minimum payload size so the whole frame (MACs + tag + payload + FCS)
+/// reaches the IEEE 802.3 64-byte floor; real NICs pad short frames up to
+/// this before transmitting.
+const MIN_PAYLOAD_LEN: usize = 46;
+
+/// Default maximum payload size — the standard Ethernet MTU. Jumbo frames
+/// need a larger limit; see [`Decoder::with_jumbo_frames`].
+const MAX_PAYLOAD_LEN: usize = 1500;
+
+/// Everything that can go wrong while decoding a frame: bad framing bytes, a
+/// failed FCS check, or a payload length outside the range Ethernet allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EthError {
+    BadPreamble,
+    BadSfd,
+    CrcMismatch { computed: u32, expected: u32 },
+    RuntFrame { len: usize },
+    OversizeFrame { len: usize },
+}
+
+impl std::fmt::Display for EthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            EthError::BadPreamble => write!(f, "preamble byte did not match the expected pattern"),
+            EthError::BadSfd => write!(f, "start frame delimiter byte was not {:#04x}", SFD),
+            EthError::CrcMismatch { computed, expected } => write!(
+                f,
+                "CRC-32 residue mismatch: computed {:#010x}, expected {:#010x}",
+                computed, expected
+            ),
+            EthError::RuntFrame { len } => {
+                write!(f, "payload too short: {} bytes (minimum {})", len, MIN_PAYLOAD_LEN)
+            }
+            EthError::OversizeFrame { len } => write!(f, "payload too large: {} bytes", len),
+        }
+    }
+}
+
+impl std::error::Error for EthError {}
+
+/// Whether the current frame's payload boundary is known up front (an IEEE
+/// 802.3 length field) or has to be discovered by watching for a CRC-32
+/// residue match, because an Ethernet II EtherType carries no length at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PayloadMode {
+    Fixed,
+    CrcTerminated,
+}
+
+struct Decoder<P: Sink + Default = Vec<u8>> {
     pos: usize,
     interim: [u8; 8],
     payload_len: usize,
-    frame: EthFrame,
+    payload_received: usize,
+    payload_mode: PayloadMode,
+    trailer_window: [u8; 4],
+    trailer_window_len: usize,
+    max_payload_len: usize,
+    crc: crc32fast::Hasher,
+    last_crc_residue: Option<u32>,
+    frame: EthFrame<P>,
     state: DecodeState,
 }
 
-impl Decoder {
+impl<P: Sink + Default> Decoder<P> {
     pub fn new() -> Self {
+        Self::with_jumbo_frames(MAX_PAYLOAD_LEN)
+    }
+
+    /// Same as [`Decoder::new`], but accepts payloads up to `max_payload_len`
+    /// bytes instead of the standard 1500-byte MTU.
+    pub fn with_jumbo_frames(max_payload_len: usize) -> Self {
         Decoder {
             pos: 0,
             state: DecodeState::Waiting,
             interim: Default::default(),
             payload_len: 0,
+            payload_received: 0,
+            payload_mode: PayloadMode::Fixed,
+            trailer_window: [0; 4],
+            trailer_window_len: 0,
+            max_payload_len,
+            crc: crc32fast::Hasher::new_with_initial(0xFFFFFFFF),
+            last_crc_residue: None,
             frame: EthFrame::default(),
         }
     }
 
-    pub fn recv_byte(&mut self, byte: u8) -> Option<EthFrame> {
+    /// The CRC-32 residue computed for the most recently completed frame
+    /// (valid or invalid). A correct frame always leaves behind
+    /// [`CRC32_RESIDUE`]; anything else means the FCS check failed.
+    pub fn last_crc_residue(&self) -> Option<u32> {
+        self.last_crc_residue
+    }
+
+    pub fn recv_byte(&mut self, byte: u8) -> Result<Option<EthFrame<P>>, EthError> {
         use DecodeState::*;
         match self.state {
             Waiting => self.step_waiting(byte),
-            Preamble => self.step_preamble(byte),
-            Sfd => self.step_sfd(byte),
+            Preamble => self.step_preamble(byte)?,
+            Sfd => self.step_sfd(byte)?,
             RxMac => self.step_rx_mac(byte),
             TxMac => self.step_tx_mac(byte),
-            Tag802 => self.step_tag802(byte),
-            Payload => self.step_payload(byte),
-            Checksum => self.step_checksum(byte),
-            Finished => return self.step_finished(),
+            Tag802 => self.step_tag802(byte)?,
+            Payload => self.step_payload(byte)?,
+            Checksum => self.step_checksum(byte)?,
+            Finished => return Ok(self.step_finished()),
             Invalid => {
-                // TODO: Handle invalid frame
-                self.state = DecodeState::Waiting;
+                // Re-dispatch this byte as the start of a fresh preamble
+                // search instead of dropping it: otherwise it's permanently
+                // missing from the next frame's preamble count and the
+                // decoder can never re-lock onto the wire.
                 self.clear_all();
-                println!("Invalid frame");
+                self.step_waiting(byte);
             }
         }
 
-        None
+        // A step above may have just completed the frame (the last FCS byte
+        // satisfying the CRC residue, or the CRC-terminated payload path
+        // finding its boundary). Hand it back immediately instead of waiting
+        // for an unrelated byte from the next frame to flush it out.
+        if matches!(self.state, Finished) {
+            return Ok(self.step_finished());
+        }
+
+        Ok(None)
     }
 
     fn step_waiting(&mut self, b: u8) {
@@ -76,11 +330,11 @@ impl Decoder {
         self.pos += 1;
     }
 
-    fn step_preamble(&mut self, b: u8) {
+    fn step_preamble(&mut self, b: u8) -> Result<(), EthError> {
         if PREAMBLE[self.pos] != b {
             self.state = DecodeState::Waiting;
             self.clear_pos_and_interim();
-            return;
+            return Err(EthError::BadPreamble);
         }
 
         self.pos += 1;
@@ -88,13 +342,17 @@ impl Decoder {
             self.clear_pos_and_interim();
             self.state = DecodeState::Sfd;
         }
+
+        Ok(())
     }
 
-    fn step_sfd(&mut self, b: u8) {
+    fn step_sfd(&mut self, b: u8) -> Result<(), EthError> {
         if b == SFD {
             self.state = DecodeState::RxMac;
+            Ok(())
         } else {
             self.state = DecodeState::Waiting;
+            Err(EthError::BadSfd)
         }
     }
 
@@ -103,6 +361,7 @@ impl Decoder {
         self.pos += 1;
 
         if self.pos == self.frame.rx_mac.len() {
+            self.crc.update(&self.frame.rx_mac);
             self.clear_pos_and_interim();
             self.state = DecodeState::TxMac;
         }
@@ -113,70 +372,185 @@ impl Decoder {
         self.pos += 1;
 
         if self.pos == self.frame.tx_mac.len() {
+            self.crc.update(&self.frame.tx_mac);
             self.clear_pos_and_interim();
             self.state = DecodeState::Tag802;
         }
     }
 
-    fn step_tag802(&mut self, b: u8) {
-        let tag = match self.frame.tag802.as_mut() {
-            None => return,
-            Some(tag) => tag,
-        };
-
-        tag[self.pos] = b;
+    fn step_tag802(&mut self, b: u8) -> Result<(), EthError> {
+        self.interim[self.pos] = b;
         self.pos += 1;
 
-        if self.pos == tag.len() {
-            let n = u16::from_be_bytes(*tag);
-            self.payload_len = n as usize;
-            self.state = DecodeState::Payload;
-            self.clear_pos_and_interim();
+        if self.pos < 2 {
+            return Ok(());
+        }
+
+        let field = u16::from_be_bytes([self.interim[0], self.interim[1]]);
+
+        if field == ETHERTYPE_VLAN {
+            if self.pos < 6 {
+                return Ok(());
+            }
+
+            self.crc.update(&self.interim[0..6]);
+
+            let tci = u16::from_be_bytes([self.interim[2], self.interim[3]]);
+            let ethertype = u16::from_be_bytes([self.interim[4], self.interim[5]]);
+
+            self.frame.tag = Tag::Vlan {
+                pcp: (tci >> 13) as u8,
+                dei: (tci >> 12) & 1 == 1,
+                vid: tci & 0x0fff,
+                ethertype,
+            };
+
+            return self.enter_unbounded_payload();
+        }
+
+        self.crc.update(&self.interim[0..2]);
+
+        if field >= ETHERTYPE_THRESHOLD {
+            self.frame.tag = Tag::Ethernet2 { ethertype: field };
+            self.enter_unbounded_payload()
+        } else {
+            self.frame.tag = Tag::None(field);
+            self.enter_payload(field as usize)
         }
     }
 
-    fn step_payload(&mut self, b: u8) {
-        self.frame.payload.push(b);
-        if self.frame.payload.len() == self.payload_len {
-            self.state = DecodeState::Checksum;
+    /// Moves into the `Payload` state once the length field has been read,
+    /// after checking it against the Ethernet minimum and maximum instead of
+    /// trusting it unconditionally.
+    fn enter_payload(&mut self, payload_len: usize) -> Result<(), EthError> {
+        self.clear_pos_and_interim();
+
+        if payload_len < MIN_PAYLOAD_LEN {
+            self.state = DecodeState::Invalid;
+            return Err(EthError::RuntFrame { len: payload_len });
+        }
+
+        if payload_len > self.max_payload_len {
+            self.state = DecodeState::Invalid;
+            return Err(EthError::OversizeFrame { len: payload_len });
         }
+
+        self.payload_len = payload_len;
+        self.payload_mode = PayloadMode::Fixed;
+        self.state = DecodeState::Payload;
+        Ok(())
     }
 
-    fn step_checksum(&mut self, b: u8) {
-        self.interim[self.pos] = b;
+    /// Moves into the `Payload` state for a frame whose length can't be read
+    /// off the wire: an Ethernet II EtherType is a protocol identifier, not a
+    /// byte count, and real hardware finds the end of such a frame via the
+    /// physical-layer end-of-frame signal, which this byte-stream decoder
+    /// doesn't have. Instead, the payload boundary is discovered the same way
+    /// the FCS itself is verified: keep accumulating bytes and check after
+    /// every byte whether treating the most recent 4 bytes as the FCS would
+    /// satisfy the CRC-32 residue check. A legitimate payload matching the
+    /// residue by coincidence is as unlikely as any other undetected CRC-32
+    /// corruption. A match found before the Ethernet minimum is reached is
+    /// reported as a runt frame rather than silently accepted.
+    fn enter_unbounded_payload(&mut self) -> Result<(), EthError> {
+        self.clear_pos_and_interim();
+        self.payload_len = 0;
+        self.payload_mode = PayloadMode::CrcTerminated;
+        self.trailer_window_len = 0;
+        self.state = DecodeState::Payload;
+        Ok(())
+    }
+
+    fn step_payload(&mut self, b: u8) -> Result<(), EthError> {
+        match self.payload_mode {
+            PayloadMode::Fixed => {
+                self.frame.payload.write_byte(b);
+                self.payload_received += 1;
+                self.crc.update(&[b]);
+
+                if self.payload_received == self.payload_len {
+                    self.state = DecodeState::Checksum;
+                }
+                Ok(())
+            }
+            PayloadMode::CrcTerminated => self.step_payload_crc_terminated(b),
+        }
+    }
+
+    fn step_payload_crc_terminated(&mut self, b: u8) -> Result<(), EthError> {
+        if self.trailer_window_len == 4 {
+            let oldest = self.trailer_window[0];
+            self.frame.payload.write_byte(oldest);
+            self.crc.update(&[oldest]);
+            self.payload_received += 1;
+            self.trailer_window.copy_within(1..4, 0);
+            self.trailer_window[3] = b;
+        } else {
+            self.trailer_window[self.trailer_window_len] = b;
+            self.trailer_window_len += 1;
+        }
+
+        if self.payload_received > self.max_payload_len {
+            self.state = DecodeState::Invalid;
+            return Err(EthError::OversizeFrame { len: self.payload_received });
+        }
+
+        if self.trailer_window_len == 4 {
+            let mut trial = self.crc.clone();
+            trial.update(&self.trailer_window);
+            let residue = trial.finalize();
+            if residue == CRC32_RESIDUE {
+                self.last_crc_residue = Some(residue);
+
+                if self.payload_received < MIN_PAYLOAD_LEN {
+                    self.state = DecodeState::Invalid;
+                    return Err(EthError::RuntFrame { len: self.payload_received });
+                }
+
+                self.state = DecodeState::Finished;
+                self.trailer_window_len = 0;
+                self.pos = 0;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn step_checksum(&mut self, b: u8) -> Result<(), EthError> {
+        // The FCS is transmitted least-significant-byte first, which makes
+        // reassembling it into an integer and comparing easy to get backwards.
+        // Folding the received bytes straight into the running hash and
+        // checking the well-known residue sidesteps that entirely.
+        self.crc.update(&[b]);
         self.pos += 1;
 
         if self.pos == 4 {
-            let crc_32 = self.hash_crc32();
-            let crc: [u8; 4] = self.interim[0..4].try_into().unwrap();
-            let verify = u32::from_be_bytes(crc);
-            println!("crc_32: {}, got: {}", crc_32, verify);
+            let residue = self.crc.clone().finalize();
+            self.last_crc_residue = Some(residue);
 
-            if crc_32 == verify {
+            if residue == CRC32_RESIDUE {
                 self.state = DecodeState::Finished;
                 self.clear_pos_and_interim();
-            } else {
-                self.state = DecodeState::Invalid;
+                return Ok(());
             }
+
+            self.state = DecodeState::Invalid;
+            return Err(EthError::CrcMismatch {
+                computed: residue,
+                expected: CRC32_RESIDUE,
+            });
         }
+
+        Ok(())
     }
 
-    fn step_finished(&mut self) -> Option<EthFrame> {
-        let frame = mem::replace(&mut self.frame, EthFrame::default());
+    fn step_finished(&mut self) -> Option<EthFrame<P>> {
+        let frame = mem::take(&mut self.frame);
         self.clear_all();
         self.state = DecodeState::Waiting;
         Some(frame)
     }
 
-    fn hash_crc32(&self) -> u32 {
-        let mut hasher = crc32fast::Hasher::new_with_initial(0xFFFFFFFF);
-        hasher.update(&self.frame.rx_mac);
-        hasher.update(&self.frame.tx_mac);
-        hasher.update(&self.frame.tag802.unwrap());
-        hasher.update(&self.frame.payload);
-        hasher.finalize()
-    }
-
     fn clear_pos_and_interim(&mut self) {
         self.pos = 0;
         self.interim = Default::default();
@@ -185,24 +559,264 @@ impl Decoder {
     fn clear_all(&mut self) {
         self.clear_pos_and_interim();
         self.payload_len = 0;
+        self.payload_received = 0;
+        self.payload_mode = PayloadMode::Fixed;
+        self.trailer_window = [0; 4];
+        self.trailer_window_len = 0;
+        self.crc = crc32fast::Hasher::new_with_initial(0xFFFFFFFF);
+        self.frame = EthFrame::default();
+    }
+}
+
+/// Computes the CRC-32 that covers the MACs, the tag/length/EtherType field
+/// (and its VLAN extension, if present) and the payload. This is the exact
+/// set of bytes the FCS trailer is taken over. The [`Encoder`] uses it to
+/// build an outgoing trailer; the [`Decoder`] does not call it, and instead
+/// feeds the same bytes, in the same order and with the same initial value,
+/// into a running hasher as they arrive.
+fn hash_crc32(rx_mac: &[u8; 6], tx_mac: &[u8; 6], tag_bytes: &[u8], payload: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new_with_initial(0xFFFFFFFF);
+    hasher.update(rx_mac);
+    hasher.update(tx_mac);
+    hasher.update(tag_bytes);
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+/// What the 2 bytes after the source MAC turned out to mean: a plain IEEE
+/// 802.3 length (the raw value is kept so it can be re-encoded as-is), a bare
+/// Ethernet II EtherType, or an EtherType behind an 802.1Q VLAN tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tag {
+    None(u16),
+    Ethernet2 { ethertype: u16 },
+    Vlan { pcp: u8, dei: bool, vid: u16, ethertype: u16 },
+}
+
+impl Default for Tag {
+    fn default() -> Self {
+        Tag::None(0)
+    }
+}
+
+impl FrameEncode for Tag {
+    fn frame_encode<S: Sink>(&self, out: &mut S) {
+        match *self {
+            Tag::None(len) => {
+                out.write_bytes(&len.to_be_bytes());
+            }
+            Tag::Ethernet2 { ethertype } => {
+                out.write_bytes(&ethertype.to_be_bytes());
+            }
+            Tag::Vlan { pcp, dei, vid, ethertype } => {
+                let tci = ((pcp as u16) << 13) | ((dei as u16) << 12) | (vid & 0x0fff);
+                out.write_bytes(&ETHERTYPE_VLAN.to_be_bytes());
+                out.write_bytes(&tci.to_be_bytes());
+                out.write_bytes(&ethertype.to_be_bytes());
+            }
+        }
+    }
+}
+
+/// Serializes a type into the on-wire byte sequence understood by [`Decoder`],
+/// writing into any [`Sink`] rather than assuming a heap-allocated `Vec<u8>`.
+///
+/// Analogous to rust-bitcoin's `ConsensusEncodable`: a small, composable trait
+/// so framing logic for a type lives next to the type itself instead of being
+/// locked inside the state machine that reads it back.
+trait FrameEncode {
+    fn frame_encode<S: Sink>(&self, out: &mut S);
+}
+
+/// The mirror image of [`FrameEncode`]: reconstructs a type from its on-wire
+/// byte sequence. For [`EthFrame`] this just drives a fresh [`Decoder`], so
+/// the decoder remains the single source of truth for what counts as valid
+/// framing.
+trait FrameDecode: Sized {
+    fn frame_decode(bytes: &[u8]) -> Option<Self>;
+}
+
+impl<P: AsRef<[u8]>> FrameEncode for EthFrame<P> {
+    fn frame_encode<S: Sink>(&self, out: &mut S) {
+        out.write_bytes(PREAMBLE);
+        out.write_byte(SFD);
+        out.write_bytes(&self.rx_mac);
+        out.write_bytes(&self.tx_mac);
+        self.tag.frame_encode(out);
+        out.write_bytes(self.payload.as_ref());
+
+        let mut tag_bytes = Vec::new();
+        self.tag.frame_encode(&mut tag_bytes);
+        let crc = hash_crc32(&self.rx_mac, &self.tx_mac, &tag_bytes, self.payload.as_ref());
+        // The FCS is transmitted least-significant-byte first.
+        out.write_bytes(&crc.to_le_bytes());
+    }
+}
+
+impl FrameDecode for EthFrame {
+    fn frame_decode(bytes: &[u8]) -> Option<Self> {
+        let mut decoder: Decoder<Vec<u8>> = Decoder::new();
+        let mut frame = None;
+
+        for &b in bytes {
+            if let Ok(Some(f)) = decoder.recv_byte(b) {
+                frame = Some(f);
+            }
+        }
+
+        frame
+    }
+}
+
+/// Builds the full on-wire byte sequence for an [`EthFrame`]: preamble, SFD,
+/// MACs, the optional 802 tag, the payload and a freshly computed CRC-32
+/// trailer. The mirror image of [`Decoder`], which walks this same sequence
+/// one byte at a time.
+struct Encoder;
+
+impl Encoder {
+    pub fn encode<P: AsRef<[u8]>>(frame: &EthFrame<P>) -> Vec<u8> {
+        let mut out = Vec::with_capacity(PREAMBLE_LEN + 1 + 6 + 6 + 2 + frame.payload.as_ref().len() + 4);
+        frame.frame_encode(&mut out);
+        out
+    }
+
+    pub fn encode_into<P: AsRef<[u8]>, S: Sink>(frame: &EthFrame<P>, sink: &mut S) {
+        frame.frame_encode(sink);
+    }
+}
+
+/// Default size of the internal buffer [`FrameReader`] and [`AsyncFrameReader`]
+/// read into before pumping the bytes through their [`Decoder`].
+const READER_BUF_LEN: usize = 4096;
+
+/// Drives a [`Decoder`] over a `std::io::Read`, so a caller gets whole frames
+/// out of an `Iterator` instead of having to feed the state machine one byte
+/// at a time itself.
+struct FrameReader<R> {
+    reader: R,
+    decoder: Decoder<Vec<u8>>,
+    buf: [u8; READER_BUF_LEN],
+    buf_pos: usize,
+    buf_len: usize,
+}
+
+impl<R: io::Read> FrameReader<R> {
+    pub fn new(reader: R) -> Self {
+        FrameReader {
+            reader,
+            decoder: Decoder::new(),
+            buf: [0; READER_BUF_LEN],
+            buf_pos: 0,
+            buf_len: 0,
+        }
+    }
+}
+
+impl<R: io::Read> Iterator for FrameReader<R> {
+    type Item = Result<EthFrame, EthError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while self.buf_pos < self.buf_len {
+                let b = self.buf[self.buf_pos];
+                self.buf_pos += 1;
+
+                match self.decoder.recv_byte(b) {
+                    Ok(Some(frame)) => return Some(Ok(frame)),
+                    Ok(None) => {}
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+
+            self.buf_len = match self.reader.read(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(n) => n,
+                Err(_) => return None,
+            };
+            self.buf_pos = 0;
+        }
+    }
+}
+
+/// The async mirror of [`FrameReader`], gated behind the `async` feature so
+/// the `futures` dependency it needs stays optional. Pumps bytes from an
+/// `AsyncRead` through an internal [`Decoder`] and exposes the result as a
+/// `Stream` of frames instead of requiring the caller to poll byte-by-byte.
+#[cfg(feature = "async")]
+struct AsyncFrameReader<R> {
+    reader: R,
+    decoder: Decoder<Vec<u8>>,
+    buf: [u8; READER_BUF_LEN],
+    buf_pos: usize,
+    buf_len: usize,
+}
+
+#[cfg(feature = "async")]
+impl<R: futures::io::AsyncRead + Unpin> AsyncFrameReader<R> {
+    pub fn new(reader: R) -> Self {
+        AsyncFrameReader {
+            reader,
+            decoder: Decoder::new(),
+            buf: [0; READER_BUF_LEN],
+            buf_pos: 0,
+            buf_len: 0,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: futures::io::AsyncRead + Unpin> futures::stream::Stream for AsyncFrameReader<R> {
+    type Item = Result<EthFrame, EthError>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        use std::task::Poll;
+
+        let this = self.get_mut();
+
+        loop {
+            while this.buf_pos < this.buf_len {
+                let b = this.buf[this.buf_pos];
+                this.buf_pos += 1;
+
+                match this.decoder.recv_byte(b) {
+                    Ok(Some(frame)) => return Poll::Ready(Some(Ok(frame))),
+                    Ok(None) => {}
+                    Err(e) => return Poll::Ready(Some(Err(e))),
+                }
+            }
+
+            match std::pin::Pin::new(&mut this.reader).poll_read(cx, &mut this.buf) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(None),
+                Poll::Ready(Ok(n)) => {
+                    this.buf_len = n;
+                    this.buf_pos = 0;
+                }
+                Poll::Ready(Err(_)) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
     }
 }
 
 #[derive(Debug)]
-struct EthFrame {
+struct EthFrame<P = Vec<u8>> {
     rx_mac: [u8; 6],
     tx_mac: [u8; 6],
-    tag802: Option<[u8; 2]>,
-    payload: Vec<u8>,
+    tag: Tag,
+    payload: P,
 }
 
-impl Default for EthFrame {
+impl<P: Default> Default for EthFrame<P> {
     fn default() -> Self {
         Self {
             rx_mac: Default::default(),
             tx_mac: Default::default(),
-            tag802: Some(Default::default()),
-            payload: Vec::with_capacity(1500),
+            tag: Tag::default(),
+            payload: P::default(),
         }
     }
 }
@@ -220,6 +834,312 @@ enum DecodeState {
     Invalid,
 }
 
+#[cfg(test)]
+mod tag_tests {
+    use super::*;
+
+    fn roundtrip(frame: &EthFrame) -> EthFrame {
+        let bytes = Encoder::encode(frame);
+        EthFrame::frame_decode(&bytes).expect("encoded frame should decode")
+    }
+
+    #[test]
+    fn length_framed_tag_survives_roundtrip() {
+        let frame = EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::None(46),
+            payload: vec![0xAB; 46],
+        };
+
+        let decoded = roundtrip(&frame);
+        assert_eq!(decoded.tag, Tag::None(46));
+        assert_eq!(decoded.payload, frame.payload);
+    }
+
+    #[test]
+    fn ethernet2_tag_does_not_use_ethertype_as_length() {
+        let frame = EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::Ethernet2 { ethertype: 0x0800 },
+            payload: vec![0xCD; MIN_PAYLOAD_LEN],
+        };
+
+        let decoded = roundtrip(&frame);
+        assert_eq!(decoded.tag, Tag::Ethernet2 { ethertype: 0x0800 });
+        assert_eq!(decoded.payload, frame.payload);
+    }
+
+    #[test]
+    fn vlan_tag_roundtrips_with_unbounded_payload() {
+        let frame = EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::Vlan {
+                pcp: 5,
+                dei: true,
+                vid: 42,
+                ethertype: 0x86DD,
+            },
+            payload: vec![0xEF; MIN_PAYLOAD_LEN + 10],
+        };
+
+        let decoded = roundtrip(&frame);
+        assert_eq!(decoded.tag, frame.tag);
+        assert_eq!(decoded.payload, frame.payload);
+    }
+}
+
+#[cfg(test)]
+mod crc_tests {
+    use super::*;
+
+    #[test]
+    fn decoder_accepts_its_own_encoders_output() {
+        let frame = EthFrame {
+            rx_mac: [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF],
+            tx_mac: [0x11, 0x22, 0x33, 0x44, 0x55, 0x66],
+            tag: Tag::None(46),
+            payload: vec![0x42; 46],
+        };
+
+        let bytes = Encoder::encode(&frame);
+        let decoded = EthFrame::frame_decode(&bytes).expect("round trip should decode");
+        assert_eq!(decoded.rx_mac, frame.rx_mac);
+        assert_eq!(decoded.tx_mac, frame.tx_mac);
+        assert_eq!(decoded.payload, frame.payload);
+    }
+
+    #[test]
+    fn corrupted_payload_is_rejected_as_crc_mismatch() {
+        let frame = EthFrame {
+            rx_mac: [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF],
+            tx_mac: [0x11, 0x22, 0x33, 0x44, 0x55, 0x66],
+            tag: Tag::None(46),
+            payload: vec![0x42; 46],
+        };
+
+        let mut bytes = Encoder::encode(&frame);
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+
+        let mut decoder: Decoder<Vec<u8>> = Decoder::new();
+        let mut saw_mismatch = false;
+        for &b in &bytes {
+            if let Err(EthError::CrcMismatch { .. }) = decoder.recv_byte(b) {
+                saw_mismatch = true;
+            }
+        }
+        assert!(saw_mismatch);
+    }
+}
+
+#[cfg(test)]
+mod encoder_tests {
+    use super::*;
+
+    fn sample_frame() -> EthFrame {
+        EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::None(46),
+            payload: vec![0x7A; 46],
+        }
+    }
+
+    #[test]
+    fn encode_into_fixed_sink_matches_encode() {
+        let frame = sample_frame();
+        let expected = Encoder::encode(&frame);
+
+        let mut sink: FixedSink<128> = FixedSink::default();
+        Encoder::encode_into(&frame, &mut sink);
+
+        assert_eq!(sink.filled(), expected.as_slice());
+        assert_eq!(sink.overflowed, 0);
+    }
+
+    #[test]
+    fn fixed_sink_tracks_overflow_instead_of_panicking() {
+        let frame = sample_frame();
+
+        let mut sink: FixedSink<4> = FixedSink::default();
+        Encoder::encode_into(&frame, &mut sink);
+
+        assert_eq!(sink.filled().len(), 4);
+        assert!(sink.overflowed > 0);
+    }
+
+    #[test]
+    fn encode_into_io_sink_writes_the_same_bytes() {
+        let frame = sample_frame();
+        let expected = Encoder::encode(&frame);
+
+        let mut buf = Vec::new();
+        let mut sink = IoSink::new(&mut buf);
+        Encoder::encode_into(&frame, &mut sink);
+
+        assert_eq!(buf, expected);
+    }
+}
+
+#[cfg(test)]
+mod error_tests {
+    use super::*;
+
+    fn feed(decoder: &mut Decoder<Vec<u8>>, bytes: &[u8]) -> Vec<Result<Option<EthFrame>, EthError>> {
+        bytes.iter().map(|&b| decoder.recv_byte(b)).collect()
+    }
+
+    #[test]
+    fn runt_frame_is_rejected() {
+        let frame = EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::None(10),
+            payload: vec![0; 10],
+        };
+        let bytes = Encoder::encode(&frame);
+
+        let mut decoder: Decoder<Vec<u8>> = Decoder::new();
+        let results = feed(&mut decoder, &bytes);
+        assert!(results.iter().any(|r| matches!(r, Err(EthError::RuntFrame { len: 10 }))));
+    }
+
+    // 1520 sits above the default 1500-byte MTU but below 0x0600 (1536), so it
+    // is still unambiguously an IEEE 802.3 length field rather than an
+    // EtherType.
+    const OVERSIZE_LEN: u16 = 1520;
+
+    #[test]
+    fn oversize_frame_is_rejected() {
+        let frame = EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::None(OVERSIZE_LEN),
+            payload: vec![0; OVERSIZE_LEN as usize],
+        };
+        let bytes = Encoder::encode(&frame);
+
+        let mut decoder: Decoder<Vec<u8>> = Decoder::new();
+        let results = feed(&mut decoder, &bytes);
+        assert!(results.iter().any(|r| matches!(
+            r,
+            Err(EthError::OversizeFrame { len }) if *len == OVERSIZE_LEN as usize
+        )));
+    }
+
+    #[test]
+    fn jumbo_frame_decoder_accepts_larger_payload() {
+        let frame = EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::None(OVERSIZE_LEN),
+            payload: vec![0; OVERSIZE_LEN as usize],
+        };
+        let bytes = Encoder::encode(&frame);
+
+        let mut decoder: Decoder<Vec<u8>> = Decoder::with_jumbo_frames(9000);
+        let results = feed(&mut decoder, &bytes);
+        assert!(results.into_iter().any(|r| matches!(r, Ok(Some(_)))));
+    }
+
+    #[test]
+    fn runt_frame_is_rejected_for_unbounded_ethernet2_payload() {
+        let frame = EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::Ethernet2 { ethertype: 0x0800 },
+            payload: vec![0; 10],
+        };
+        let bytes = Encoder::encode(&frame);
+
+        let mut decoder: Decoder<Vec<u8>> = Decoder::new();
+        let results = feed(&mut decoder, &bytes);
+        assert!(results.iter().any(|r| matches!(r, Err(EthError::RuntFrame { len: 10 }))));
+    }
+
+    #[test]
+    fn decoder_resyncs_on_a_valid_frame_after_a_corrupted_one() {
+        let corrupted_frame = EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::None(46),
+            payload: vec![0xAA; 46],
+        };
+        let mut corrupted_bytes = Encoder::encode(&corrupted_frame);
+        let last = corrupted_bytes.len() - 1;
+        corrupted_bytes[last] ^= 0xFF;
+
+        let valid_frame = EthFrame {
+            rx_mac: [7, 8, 9, 10, 11, 12],
+            tx_mac: [12, 11, 10, 9, 8, 7],
+            tag: Tag::None(46),
+            payload: vec![0xBB; 46],
+        };
+        let valid_bytes = Encoder::encode(&valid_frame);
+
+        let mut wire = corrupted_bytes;
+        wire.extend_from_slice(&valid_bytes);
+
+        let mut decoder: Decoder<Vec<u8>> = Decoder::new();
+        let results = feed(&mut decoder, &wire);
+
+        assert!(results.iter().any(|r| matches!(r, Err(EthError::CrcMismatch { .. }))));
+        let decoded = results.into_iter().find_map(|r| r.ok().flatten());
+        let decoded = decoded.expect("the second, valid frame should still decode");
+        assert_eq!(decoded.payload, valid_frame.payload);
+    }
+
+    #[test]
+    fn frame_reader_yields_decoded_frames_from_a_reader() {
+        let frame = EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::None(46),
+            payload: vec![0x99; 46],
+        };
+        let bytes = Encoder::encode(&frame);
+
+        let mut reader = FrameReader::new(bytes.as_slice());
+        let decoded = reader.next().expect("should yield one frame").expect("should decode");
+        assert_eq!(decoded.payload, frame.payload);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn bad_preamble_is_reported() {
+        let mut decoder: Decoder<Vec<u8>> = Decoder::new();
+        let results = feed(&mut decoder, &[0x55, 0x55, 0x00]);
+        assert!(results.iter().any(|r| matches!(r, Err(EthError::BadPreamble))));
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+mod async_reader_tests {
+    use super::*;
+    use futures::stream::StreamExt;
+
+    #[test]
+    fn async_frame_reader_yields_decoded_frames_from_an_async_reader() {
+        let frame = EthFrame {
+            rx_mac: [1, 2, 3, 4, 5, 6],
+            tx_mac: [6, 5, 4, 3, 2, 1],
+            tag: Tag::None(46),
+            payload: vec![0x42; 46],
+        };
+        let bytes = Encoder::encode(&frame);
+
+        let mut reader = AsyncFrameReader::new(futures::io::Cursor::new(bytes));
+        let decoded = futures::executor::block_on(reader.next())
+            .expect("should yield one frame")
+            .expect("should decode");
+        assert_eq!(decoded.payload, frame.payload);
+        assert!(futures::executor::block_on(reader.next()).is_none());
+    }
+}
+
 // enum Decoder {
 //     Waiting,
 //     Preamble{